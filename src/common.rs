@@ -2,11 +2,10 @@ use crate::{
     error::Error,
     ip2location::{db::LocationDB, record::LocationRecord},
     ip2proxy::{db::ProxyDB, record::ProxyRecord},
+    maxminddb::db::MaxMindDb,
 };
 use memmap::Mmap;
 use std::{
-    fs::File,
-    io::{Read, Seek, SeekFrom},
     net::{IpAddr, Ipv6Addr},
     path::{Path, PathBuf},
 };
@@ -21,6 +20,7 @@ pub const TO_TEREDO: u128 = 0x2001_0000_ffff_ffff_ffff_ffff_ffff_ffff;
 pub enum DB {
     LocationDb(LocationDB),
     ProxyDb(ProxyDB),
+    MaxMindDb(MaxMindDb),
 }
 
 #[derive(Debug)]
@@ -31,7 +31,10 @@ pub enum Record {
 
 #[derive(Debug)]
 pub(crate) enum Source {
-    File(PathBuf, File),
+    // The whole file is read into memory once at open so every read helper is
+    // plain slice indexing: no per-query syscall and no shared cursor, which is
+    // what makes a `&self` lookup safe to share across threads.
+    File(PathBuf, Vec<u8>),
     Mmap(PathBuf, Mmap),
 }
 
@@ -45,82 +48,41 @@ impl std::fmt::Display for Source {
 }
 
 impl Source {
-    pub fn read_u8(&mut self, offset: u64) -> Result<u8, Error> {
+    // The whole file as a byte slice, whether file-backed (cached at open) or
+    // memory mapped. Read helpers index into this directly.
+    fn bytes(&self) -> &[u8] {
         match self {
-            Source::File(_, f) => {
-                f.seek(SeekFrom::Start(offset - 1))?;
-                let mut buf = [0_u8; 1];
-                f.read(&mut buf)?;
-                Ok(buf[0])
-            }
-            Source::Mmap(_, m) => Ok(m[(offset - 1) as usize]),
+            Source::File(_, b) => b,
+            Source::Mmap(_, m) => &m[..],
         }
     }
 
-    pub fn read_u32(&mut self, offset: u64) -> Result<u32, Error> {
-        match self {
-            Source::File(_, f) => {
-                f.seek(SeekFrom::Start(offset - 1))?;
-                let mut buf = [0_u8; 4];
-                f.read(&mut buf)?;
-                let result = u32::from_ne_bytes(buf);
-                Ok(result)
-            }
-            Source::Mmap(_, m) => {
-                let mut buf = [0_u8; 4];
-                buf[0] = m[(offset - 1) as usize];
-                buf[1] = m[offset as usize];
-                buf[2] = m[(offset + 1) as usize];
-                buf[3] = m[(offset + 2) as usize];
-                let result = u32::from_ne_bytes(buf);
-                Ok(result)
-            }
-        }
+    pub fn read_u8(&self, offset: u64) -> Result<u8, Error> {
+        Ok(self.bytes()[(offset - 1) as usize])
     }
 
-    pub fn read_f32(&mut self, offset: u64) -> Result<f32, Error> {
-        match self {
-            Source::File(_, f) => {
-                f.seek(SeekFrom::Start(offset - 1))?;
-                let mut buf = [0_u8; 4];
-                f.read(&mut buf)?;
-                let result = f32::from_ne_bytes(buf);
-                Ok(result)
-            }
-            Source::Mmap(_, m) => {
-                let mut buf = [0_u8; 4];
-                buf[0] = m[(offset - 1) as usize];
-                buf[1] = m[offset as usize];
-                buf[2] = m[(offset + 1) as usize];
-                buf[3] = m[(offset + 2) as usize];
-                let result = f32::from_ne_bytes(buf);
-                Ok(result)
-            }
-        }
+    pub fn read_u32(&self, offset: u64) -> Result<u32, Error> {
+        let start = (offset - 1) as usize;
+        let mut buf = [0_u8; 4];
+        buf.copy_from_slice(&self.bytes()[start..start + 4]);
+        Ok(u32::from_ne_bytes(buf))
+    }
+
+    pub fn read_f32(&self, offset: u64) -> Result<f32, Error> {
+        let start = (offset - 1) as usize;
+        let mut buf = [0_u8; 4];
+        buf.copy_from_slice(&self.bytes()[start..start + 4]);
+        Ok(f32::from_ne_bytes(buf))
     }
 
-    pub fn read_str(&mut self, offset: u64) -> Result<String, Error> {
+    pub fn read_str(&self, offset: u64) -> Result<String, Error> {
         let len = self.read_u8(offset + 1)? as usize;
-        match self {
-            Source::File(_, f) => {
-                f.seek(SeekFrom::Start(offset + 1))?;
-                let mut buf = vec![0_u8; len];
-                f.read(&mut buf)?;
-                let s = String::from_utf8(buf)?;
-                Ok(s)
-            }
-            Source::Mmap(_, m) => {
-                let mut buf = vec![0_u8; len];
-                for i in 0..len {
-                    buf[i] = m[(offset + 1) as usize + i];
-                }
-                let s = String::from_utf8(buf)?;
-                Ok(s)
-            }
-        }
+        let start = (offset + 1) as usize;
+        let s = String::from_utf8(self.bytes()[start..start + len].to_vec())?;
+        Ok(s)
     }
 
-    pub fn read_ipv6(&mut self, offset: u64) -> Result<Ipv6Addr, Error> {
+    pub fn read_ipv6(&self, offset: u64) -> Result<Ipv6Addr, Error> {
         let mut buf = [0_u8; 16];
         let mut i = 0;
         let mut j = 15;
@@ -134,6 +96,29 @@ impl Source {
         let result = Ipv6Addr::from(buf);
         Ok(result)
     }
+
+    /// Reads `size` bytes starting at the absolute (zero based) `offset`.
+    ///
+    /// Unlike the other helpers this does not apply the IP2Location one based
+    /// offset convention; it is used by the MaxMind reader, whose offsets are
+    /// already zero based.
+    pub fn read_buf(&self, offset: u64, size: usize) -> Result<Vec<u8>, Error> {
+        let start = offset as usize;
+        Ok(self.bytes()[start..start + size].to_vec())
+    }
+
+    /// Returns the whole backing buffer as a borrowed slice.
+    ///
+    /// Both variants keep the full database in memory, so this never copies and
+    /// lets the MaxMind decoder work against a persistent view.
+    pub fn as_slice(&self) -> &[u8] {
+        self.bytes()
+    }
+
+    /// Total length of the backing file in bytes.
+    pub fn len(&self) -> Result<u64, Error> {
+        Ok(self.bytes().len() as u64)
+    }
 }
 
 impl DB {
@@ -158,6 +143,8 @@ impl DB {
             Ok(DB::LocationDb(location_db))
         } else if let Ok(proxy_db) = ProxyDB::from_file(&path) {
             Ok(DB::ProxyDb(proxy_db))
+        } else if let Ok(maxmind_db) = MaxMindDb::from_file(&path) {
+            Ok(DB::MaxMindDb(maxmind_db))
         } else {
             Err(Error::UnknownDb)
         }
@@ -179,6 +166,8 @@ impl DB {
             Ok(DB::LocationDb(location_db))
         } else if let Ok(proxy_db) = ProxyDB::from_file_mmap(&path) {
             Ok(DB::ProxyDb(proxy_db))
+        } else if let Ok(maxmind_db) = MaxMindDb::from_file_mmap(&path) {
+            Ok(DB::MaxMindDb(maxmind_db))
         } else {
             Err(Error::UnknownDb)
         }
@@ -198,10 +187,11 @@ impl DB {
         match self {
             Self::LocationDb(db) => db.print_db_info(),
             Self::ProxyDb(db) => db.print_db_info(),
+            Self::MaxMindDb(db) => db.print_db_info(),
         }
     }
 
-    pub fn ip_lookup(&mut self, ip: IpAddr) -> Result<Record, Error> {
+    pub fn ip_lookup(&self, ip: IpAddr) -> Result<Record, Error> {
         //! Lookup for the given IPv4 or IPv6 and returns the
         //! Geo information or Proxy Information
         //!
@@ -210,7 +200,7 @@ impl DB {
         //!```rust
         //! use ip2location::{DB, Record};
         //!
-        //! let mut db = DB::from_file("data/IP2LOCATION-LITE-DB1.IPV6.BIN").unwrap();
+        //! let db = DB::from_file("data/IP2LOCATION-LITE-DB1.IPV6.BIN").unwrap();
         //! let geo_info = db.ip_lookup("2a01:cb08:8d14::".parse().unwrap()).unwrap();
         //! println!("{:#?}", geo_info);
         //! let record = if let Record::LocationDb(rec) = geo_info {
@@ -223,6 +213,62 @@ impl DB {
         match self {
             Self::LocationDb(db) => Ok(Record::LocationDb(db.ip_lookup(ip)?)),
             Self::ProxyDb(db) => Ok(Record::ProxyDb(db.ip_lookup(ip)?)),
+            Self::MaxMindDb(db) => Ok(Record::LocationDb(db.ip_lookup(ip)?)),
+        }
+    }
+
+    /// Lookup for the given IP and augment the result with a reverse-DNS
+    /// hostname.
+    ///
+    /// Private and reserved addresses are reported as private rather than
+    /// resolved (so internal PTRs are never leaked), and hostnames matching a
+    /// configured hidden suffix are redacted. Only available with the `dns`
+    /// feature.
+    #[cfg(feature = "dns")]
+    pub fn ip_lookup_with_dns(
+        &self,
+        ip: IpAddr,
+        options: &crate::dns::DnsOptions,
+    ) -> Result<crate::dns::DnsRecord, Error> {
+        let record = self.ip_lookup(ip)?;
+        Ok(crate::dns::enrich(record, ip, options))
+    }
+
+    /// Walks the database sequentially, yielding every `(start, end, Record)`
+    /// block.
+    ///
+    /// Because the underlying data is range indexed this is far cheaper than
+    /// probing individual addresses, and lets callers stream out CIDR spans
+    /// (e.g. filtering on [`Proxy::IsAProxy`] / `IsADataCenterIpAddress` or a
+    /// `usage_type`) to feed an `nftables`/`ipset` blocklist.
+    ///
+    /// Each item is a `Result` so a corrupt database surfaces as an `Err`
+    /// rather than a silently truncated dump (the `.bin` sources never fail and
+    /// always yield `Ok`).
+    ///
+    /// ## Example usage
+    ///
+    ///```rust
+    /// use ip2location::DB;
+    ///
+    /// let db = DB::from_file("data/IP2PROXY-IP-COUNTRY.BIN").unwrap();
+    /// for block in db.iter() {
+    ///     let (start, end, _record) = block.unwrap();
+    ///     println!("{start} - {end}");
+    /// }
+    ///```
+    pub fn iter(&self) -> Box<dyn Iterator<Item = Result<(IpAddr, IpAddr, Record), Error>> + '_> {
+        match self {
+            Self::LocationDb(db) => {
+                Box::new(db.iter().map(|(s, e, r)| Ok((s, e, Record::LocationDb(r)))))
+            }
+            Self::ProxyDb(db) => {
+                Box::new(db.iter().map(|(s, e, r)| Ok((s, e, Record::ProxyDb(r)))))
+            }
+            Self::MaxMindDb(db) => Box::new(
+                db.iter()
+                    .map(|r| r.map(|(s, e, rec)| (s, e, Record::LocationDb(rec)))),
+            ),
         }
     }
 }
\ No newline at end of file
@@ -0,0 +1,79 @@
+#![cfg(feature = "dns")]
+//! Optional reverse-DNS enrichment for lookup results.
+//!
+//! This module and its `dns-lookup` dependency are only compiled with the
+//! `dns` feature, so the core lookup path is unaffected when it is disabled.
+
+use crate::common::Record;
+use std::net::IpAddr;
+
+/// Controls how [`DB::ip_lookup_with_dns`](crate::DB::ip_lookup_with_dns)
+/// resolves and filters hostnames.
+#[derive(Debug, Clone, Default)]
+pub struct DnsOptions {
+    /// Hostnames whose suffix matches any entry here are redacted to `None`
+    /// (matching is case-insensitive and ignores a trailing dot).
+    pub hidden_suffixes: Vec<String>,
+    /// Resolve a PTR even for private/reserved addresses. Off by default so
+    /// internal hostnames are never leaked.
+    pub resolve_private: bool,
+}
+
+/// A [`Record`] augmented with its reverse-DNS hostname.
+#[derive(Debug)]
+pub struct DnsRecord {
+    pub record: Record,
+    /// The resolved hostname, or `None` when the address is private, the PTR
+    /// lookup failed, or the hostname was redacted.
+    pub hostname: Option<String>,
+    /// Whether the looked-up address falls in a private or reserved range.
+    pub is_private: bool,
+}
+
+/// Augments `record` with a reverse-DNS hostname according to `options`.
+pub fn enrich(record: Record, ip: IpAddr, options: &DnsOptions) -> DnsRecord {
+    let is_private = is_private(ip);
+    let hostname = if is_private && !options.resolve_private {
+        None
+    } else {
+        reverse_lookup(ip).filter(|host| !is_hidden(host, &options.hidden_suffixes))
+    };
+    DnsRecord {
+        record,
+        hostname,
+        is_private,
+    }
+}
+
+fn reverse_lookup(ip: IpAddr) -> Option<String> {
+    dns_lookup::lookup_addr(&ip).ok()
+}
+
+fn is_hidden(hostname: &str, hidden_suffixes: &[String]) -> bool {
+    let host = hostname.trim_end_matches('.').to_ascii_lowercase();
+    hidden_suffixes
+        .iter()
+        .map(|s| s.trim_end_matches('.').to_ascii_lowercase())
+        .any(|suffix| host == suffix || host.ends_with(&format!(".{suffix}")))
+}
+
+// RFC 1918 / ULA / loopback / link-local / unspecified addresses should never
+// be resolved to a PTR record.
+fn is_private(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(v4) => {
+            v4.is_private()
+                || v4.is_loopback()
+                || v4.is_link_local()
+                || v4.is_unspecified()
+                || v4.is_broadcast()
+        }
+        IpAddr::V6(v6) => {
+            let first = v6.segments()[0];
+            v6.is_loopback()
+                || v6.is_unspecified()
+                || (first & 0xfe00) == 0xfc00 // fc00::/7  (unique local)
+                || (first & 0xffc0) == 0xfe80 // fe80::/10 (link local)
+        }
+    }
+}
@@ -0,0 +1,256 @@
+#![allow(clippy::enum_variant_names, clippy::derive_partial_eq_without_eq)]
+
+use serde::Serialize;
+use serde_with::skip_serializing_none;
+use std::fmt;
+use std::net::{IpAddr, Ipv6Addr};
+
+#[derive(PartialEq, Debug, Clone, Serialize)]
+pub struct Country {
+    pub short_name: String,
+    pub long_name: String,
+}
+
+#[skip_serializing_none]
+#[derive(PartialEq, Debug, Clone, Serialize)]
+pub struct LocationRecord {
+    pub ip: IpAddr,
+    pub latitude: Option<f32>,
+    pub longitude: Option<f32>,
+    pub country: Option<Country>,
+    pub region: Option<String>,
+    pub city: Option<String>,
+    pub isp: Option<String>,
+    pub domain: Option<String>,
+    pub zip_code: Option<String>,
+    pub time_zone: Option<String>,
+    pub net_speed: Option<String>,
+    pub idd_code: Option<String>,
+    pub area_code: Option<String>,
+    pub weather_station_code: Option<String>,
+    pub weather_station_name: Option<String>,
+    pub mcc: Option<String>,
+    pub mnc: Option<String>,
+    pub mobile_brand: Option<String>,
+    pub elevation: Option<f32>,
+    pub usage_type: Option<String>,
+    pub address_type: Option<String>,
+    pub category: Option<String>,
+    pub district: Option<String>,
+    pub asn: Option<String>,
+    pub as_: Option<String>,
+    /// The `from`-`to` bounds of the database block this record was matched in.
+    pub range: Option<(IpAddr, IpAddr)>,
+}
+
+impl LocationRecord {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(&self).unwrap()
+    }
+
+    /// Encodes the record's coordinates as an RFC 1876 DNS `LOC` record.
+    ///
+    /// Returns `None` when the record carries no latitude/longitude. The
+    /// altitude is taken from [`LocationRecord::elevation`] (metres, defaulting
+    /// to sea level) and the size/precision fields default to the coarse,
+    /// city-level resolution this data actually provides.
+    pub fn to_dns_loc(&self) -> Option<DnsLoc> {
+        // Widen the f32 degrees through their shortest decimal form, so that a
+        // stored 42.365 encodes as 42.365 and not as 42.3650017.
+        let widen = |deg: f32| deg.to_string().parse::<f64>().unwrap_or(deg as f64);
+        let latitude = self.latitude?;
+        let longitude = self.longitude?;
+        Some(DnsLoc::new(
+            widen(latitude),
+            widen(longitude),
+            self.elevation.unwrap_or(0.0) as f64,
+            DnsLoc::DEFAULT_SIZE_CM,
+            DnsLoc::DEFAULT_HORIZ_PRE_CM,
+            DnsLoc::DEFAULT_VERT_PRE_CM,
+        ))
+    }
+}
+
+/// A version-0 DNS `LOC` record (RFC 1876).
+///
+/// Latitude and longitude are thousandths of an arc-second offset from 2^31
+/// (the equator / prime meridian); altitude is centimetres offset by 100 000 m;
+/// size and the precision fields are a 4-bit mantissa and 4-bit base-10
+/// exponent giving a value in centimetres.
+#[derive(PartialEq, Debug, Clone)]
+pub struct DnsLoc {
+    pub version: u8,
+    pub size: u8,
+    pub horiz_pre: u8,
+    pub vert_pre: u8,
+    pub latitude: u32,
+    pub longitude: u32,
+    pub altitude: u32,
+}
+
+impl DnsLoc {
+    // City-level data: ~1000 m horizontal precision, altitude essentially
+    // unknown.
+    const DEFAULT_SIZE_CM: u64 = 100_000; // 1000 m enclosing sphere
+    const DEFAULT_HORIZ_PRE_CM: u64 = 100_000; // 1000 m
+    const DEFAULT_VERT_PRE_CM: u64 = 1_000_000; // 10 000 m
+
+    // Equator / prime meridian, in thousandths of an arc-second.
+    const EQUATOR: i64 = 1 << 31;
+    // Reference altitude, 100 000 m below which altitudes are negative.
+    const ALT_BASE_CM: i64 = 100_000 * 100;
+
+    fn new(lat_deg: f64, lon_deg: f64, alt_m: f64, size: u64, hp: u64, vp: u64) -> Self {
+        let latitude = (Self::EQUATOR + (lat_deg * 3_600_000.0).round() as i64)
+            .clamp(0, u32::MAX as i64) as u32;
+        let longitude = (Self::EQUATOR + (lon_deg * 3_600_000.0).round() as i64)
+            .clamp(0, u32::MAX as i64) as u32;
+        let altitude = (Self::ALT_BASE_CM + (alt_m * 100.0).round() as i64)
+            .clamp(0, u32::MAX as i64) as u32;
+        DnsLoc {
+            version: 0,
+            size: encode_precision(size),
+            horiz_pre: encode_precision(hp),
+            vert_pre: encode_precision(vp),
+            latitude,
+            longitude,
+            altitude,
+        }
+    }
+
+    /// The 16-byte on-the-wire representation (RDATA of a `LOC` record).
+    pub fn to_bytes(&self) -> [u8; 16] {
+        let mut buf = [0_u8; 16];
+        buf[0] = self.version;
+        buf[1] = self.size;
+        buf[2] = self.horiz_pre;
+        buf[3] = self.vert_pre;
+        buf[4..8].copy_from_slice(&self.latitude.to_be_bytes());
+        buf[8..12].copy_from_slice(&self.longitude.to_be_bytes());
+        buf[12..16].copy_from_slice(&self.altitude.to_be_bytes());
+        buf
+    }
+}
+
+impl fmt::Display for DnsLoc {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (lat_d, lat_m, lat_s, ns) = dms(self.latitude, 'N', 'S');
+        let (lon_d, lon_m, lon_s, ew) = dms(self.longitude, 'E', 'W');
+        let alt = (self.altitude as f64 - DnsLoc::ALT_BASE_CM as f64) / 100.0;
+        write!(
+            f,
+            "{lat_d} {lat_m} {lat_s:.3} {ns} {lon_d} {lon_m} {lon_s:.3} {ew} {alt:.2}m {size:.0}m {hp:.0}m {vp:.0}m",
+            size = decode_precision(self.size) / 100.0,
+            hp = decode_precision(self.horiz_pre) / 100.0,
+            vp = decode_precision(self.vert_pre) / 100.0,
+        )
+    }
+}
+
+// Splits a thousandths-of-arc-second coordinate into degrees, minutes, seconds
+// and the positive/negative hemisphere letter.
+fn dms(value: u32, positive: char, negative: char) -> (u64, u64, f64, char) {
+    let offset = value as i64 - DnsLoc::EQUATOR;
+    let hemisphere = if offset >= 0 { positive } else { negative };
+    let total_ms = offset.unsigned_abs();
+    let degrees = total_ms / 3_600_000;
+    let minutes = (total_ms % 3_600_000) / 60_000;
+    let seconds = (total_ms % 60_000) as f64 / 1000.0;
+    (degrees, minutes, seconds, hemisphere)
+}
+
+// Encodes a centimetre value as a 4-bit mantissa (1-9) and 4-bit base-10
+// exponent, as used by the size and precision fields.
+fn encode_precision(cm: u64) -> u8 {
+    let mut mantissa = cm;
+    let mut exponent = 0_u8;
+    while mantissa >= 10 && exponent < 9 {
+        mantissa /= 10;
+        exponent += 1;
+    }
+    let mantissa = mantissa.min(9) as u8;
+    (mantissa << 4) | exponent
+}
+
+fn decode_precision(byte: u8) -> f64 {
+    let mantissa = (byte >> 4) as f64;
+    let exponent = (byte & 0x0f) as i32;
+    mantissa * 10_f64.powi(exponent)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Harvard Square (42 21 54 N, 71 06 18 W), the worked example from RFC 1876.
+    fn sample() -> LocationRecord {
+        LocationRecord {
+            latitude: Some(42.365),
+            longitude: Some(-71.105),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn dns_loc_wire_encoding() {
+        let loc = sample().to_dns_loc().unwrap();
+
+        // Coordinates are 2^31 +/- round(degrees * 3_600_000).
+        assert_eq!(loc.latitude, 2_147_483_648 + 152_514_000);
+        assert_eq!(loc.longitude, 2_147_483_648 - 255_978_000);
+        // Sea level is the 100 000 m reference, in centimetres.
+        assert_eq!(loc.altitude, 100_000 * 100);
+
+        let mut expected = [0_u8; 16];
+        expected[0] = 0; // version 0
+        expected[1] = 0x15; // size: 1e5 cm (1000 m)
+        expected[2] = 0x15; // horiz precision: 1e5 cm (1000 m)
+        expected[3] = 0x16; // vert precision: 1e6 cm (10 000 m)
+        expected[4..8].copy_from_slice(&loc.latitude.to_be_bytes());
+        expected[8..12].copy_from_slice(&loc.longitude.to_be_bytes());
+        expected[12..16].copy_from_slice(&loc.altitude.to_be_bytes());
+        assert_eq!(loc.to_bytes(), expected);
+    }
+
+    #[test]
+    fn dns_loc_text_form() {
+        let loc = sample().to_dns_loc().unwrap();
+        assert_eq!(
+            loc.to_string(),
+            "42 21 54.000 N 71 6 18.000 W 0.00m 1000m 1000m 10000m"
+        );
+    }
+}
+
+impl Default for LocationRecord {
+    fn default() -> Self {
+        LocationRecord {
+            ip: IpAddr::V6(Ipv6Addr::UNSPECIFIED),
+            latitude: None,
+            longitude: None,
+            country: None,
+            region: None,
+            city: None,
+            isp: None,
+            domain: None,
+            zip_code: None,
+            time_zone: None,
+            net_speed: None,
+            idd_code: None,
+            area_code: None,
+            weather_station_code: None,
+            weather_station_name: None,
+            mcc: None,
+            mnc: None,
+            mobile_brand: None,
+            elevation: None,
+            usage_type: None,
+            address_type: None,
+            category: None,
+            district: None,
+            asn: None,
+            as_: None,
+            range: None,
+        }
+    }
+}
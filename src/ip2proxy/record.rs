@@ -35,6 +35,8 @@ pub struct ProxyRecord {
     pub threat: Option<String>,
     pub provider: Option<String>,
     pub usage_type: Option<String>,
+    /// The `from`-`to` bounds of the database block this record was matched in.
+    pub range: Option<(IpAddr, IpAddr)>,
 }
 
 impl ProxyRecord {
@@ -60,6 +62,7 @@ impl Default for ProxyRecord {
             threat: None,
             provider: None,
             usage_type: None,
+            range: None,
         }
     }
 }
@@ -0,0 +1,591 @@
+use crate::{
+    common::Source,
+    error::Error,
+    ip2location::record::{Country, LocationRecord},
+};
+use memmap::Mmap;
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    net::IpAddr,
+    path::Path,
+};
+
+// The metadata section is introduced by this marker, scanned backwards from
+// the end of the file (it never appears farther than 128 KiB from the end).
+const METADATA_START_MARKER: &[u8] = b"\xab\xcd\xefMaxMind.com";
+
+// The search tree and the data section are separated by this many zero bytes.
+const DATA_SECTION_SEPARATOR_SIZE: usize = 16;
+
+#[derive(Debug)]
+pub(crate) struct Metadata {
+    pub node_count: u32,
+    pub record_size: u16,
+    pub ip_version: u16,
+    pub database_type: String,
+}
+
+#[derive(Debug)]
+pub struct MaxMindDb {
+    source: Source,
+    metadata: Metadata,
+    node_byte_size: usize,
+    search_tree_size: usize,
+    // Node at which an IPv4 lookup starts in an IPv6 database (0 otherwise).
+    ipv4_start_node: usize,
+}
+
+// A decoded value from the data section. Only the subset of MaxMind's data
+// types that appears in GeoLite2/ASN payloads is modelled explicitly.
+#[derive(Debug, Clone)]
+enum MmdbValue {
+    String(String),
+    Double(f64),
+    Bytes(Vec<u8>),
+    Uint(u64),
+    Int(i32),
+    Boolean(bool),
+    Float(f32),
+    Map(BTreeMap<String, MmdbValue>),
+    Array(Vec<MmdbValue>),
+}
+
+impl MaxMindDb {
+    /// Consume the unopened db and open the `.mmdb` file.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let data = std::fs::read(&path)?;
+        let source = Source::File(path.as_ref().to_path_buf(), data);
+        Self::open(source)
+    }
+
+    /// Consume the unopened db and mmap the `.mmdb` file.
+    pub fn from_file_mmap<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let file = File::open(&path)?;
+        let mmap = unsafe { Mmap::map(&file)? };
+        let source = Source::Mmap(path.as_ref().to_path_buf(), mmap);
+        Self::open(source)
+    }
+
+    fn open(source: Source) -> Result<Self, Error> {
+        let metadata = Self::read_metadata(&source)?;
+        let node_byte_size = metadata.record_size as usize / 4;
+        let search_tree_size = metadata.node_count as usize * node_byte_size;
+        let db = MaxMindDb {
+            source,
+            metadata,
+            node_byte_size,
+            search_tree_size,
+            ipv4_start_node: 0,
+        };
+        let ipv4_start_node = db.find_ipv4_start_node()?;
+        Ok(MaxMindDb {
+            ipv4_start_node,
+            ..db
+        })
+    }
+
+    pub fn print_db_info(&self) {
+        println!("Db Type            : MaxMind");
+        println!("Database Type      : {}", self.metadata.database_type);
+        println!("IP Version         : {}", self.metadata.ip_version);
+        println!("Node Count         : {}", self.metadata.node_count);
+        println!("Record Size        : {}", self.metadata.record_size);
+    }
+
+    /// Lookup for the given IPv4 or IPv6 and return the Geo information
+    /// mapped onto the shared [`LocationRecord`].
+    pub fn ip_lookup(&self, ip: IpAddr) -> Result<LocationRecord, Error> {
+        let bits = Self::ip_to_bits(ip);
+        let mut node = match ip {
+            // An IPv4 address in an IPv6 tree starts below the ::/96 node.
+            IpAddr::V4(_) if self.metadata.ip_version == 6 => self.ipv4_start_node,
+            _ => 0,
+        };
+
+        let node_count = self.metadata.node_count as usize;
+        let mut prefix_len = 0_u8;
+        for bit in &bits {
+            if node >= node_count {
+                break;
+            }
+            node = self.read_node(node, *bit as usize)?;
+            prefix_len += 1;
+        }
+
+        let mut record = LocationRecord {
+            ip,
+            range: Some(Self::network_range(ip, prefix_len)),
+            ..Default::default()
+        };
+        if node == node_count {
+            // Reached the "no data" terminal node.
+            return Ok(record);
+        }
+        if node > node_count {
+            let offset = node - node_count + self.search_tree_size;
+            let (value, _) = self.decode(offset)?;
+            self.map_into_record(&value, &mut record);
+        }
+        Ok(record)
+    }
+
+    /// Walks the search tree and yields every populated
+    /// `Result<(start, end, record)>` network block, in address order.
+    ///
+    /// Each item is a `Result` so a corrupt database surfaces as an `Err`
+    /// rather than a silently truncated dump; the iterator stops after the
+    /// first error. Note that for an IPv6 database the IPv4 space appears as
+    /// IPv6 networks under `::/96`, not as `IpAddr::V4` CIDRs.
+    pub fn iter(&self) -> MaxMindIter<'_> {
+        MaxMindIter {
+            db: self,
+            stack: vec![(0, 0_u128, 0)],
+            done: false,
+        }
+    }
+
+    // The network spanned by the `prefix_len` bits consumed for `ip`.
+    fn network_range(ip: IpAddr, prefix_len: u8) -> (IpAddr, IpAddr) {
+        match ip {
+            IpAddr::V4(v4) => {
+                let (start, end) = mask_bounds(u32::from(v4) as u128, prefix_len, 32);
+                to_ip_pair(start, end, 32)
+            }
+            IpAddr::V6(v6) => {
+                let (start, end) = mask_bounds(u128::from(v6), prefix_len, 128);
+                to_ip_pair(start, end, 128)
+            }
+        }
+    }
+
+    // ---- search tree ------------------------------------------------------
+
+    fn ip_to_bits(ip: IpAddr) -> Vec<u8> {
+        let octets = match ip {
+            IpAddr::V4(v4) => v4.octets().to_vec(),
+            IpAddr::V6(v6) => v6.octets().to_vec(),
+        };
+        let mut bits = Vec::with_capacity(octets.len() * 8);
+        for byte in octets {
+            for shift in (0..8).rev() {
+                bits.push((byte >> shift) & 1);
+            }
+        }
+        bits
+    }
+
+    // Walk the 96 leading zero bits of the IPv6 space to reach the subtree
+    // that holds IPv4 addresses.
+    fn find_ipv4_start_node(&self) -> Result<usize, Error> {
+        if self.metadata.ip_version != 6 {
+            return Ok(0);
+        }
+        let mut node = 0usize;
+        let node_count = self.metadata.node_count as usize;
+        for _ in 0..96 {
+            if node >= node_count {
+                break;
+            }
+            node = self.read_node(node, 0)?;
+        }
+        Ok(node)
+    }
+
+    fn read_node(&self, node: usize, index: usize) -> Result<usize, Error> {
+        let base = node * self.node_byte_size;
+        let bytes = &self.bytes()[base..base + self.node_byte_size];
+        let record = match self.metadata.record_size {
+            24 => {
+                let start = index * 3;
+                ((bytes[start] as usize) << 16)
+                    | ((bytes[start + 1] as usize) << 8)
+                    | (bytes[start + 2] as usize)
+            }
+            28 => {
+                if index == 0 {
+                    (((bytes[3] as usize) & 0xf0) << 20)
+                        | ((bytes[0] as usize) << 16)
+                        | ((bytes[1] as usize) << 8)
+                        | (bytes[2] as usize)
+                } else {
+                    (((bytes[3] as usize) & 0x0f) << 24)
+                        | ((bytes[4] as usize) << 16)
+                        | ((bytes[5] as usize) << 8)
+                        | (bytes[6] as usize)
+                }
+            }
+            32 => {
+                let start = index * 4;
+                ((bytes[start] as usize) << 24)
+                    | ((bytes[start + 1] as usize) << 16)
+                    | ((bytes[start + 2] as usize) << 8)
+                    | (bytes[start + 3] as usize)
+            }
+            other => {
+                return Err(Error::IoError(format!(
+                    "Unsupported MaxMind record size: {other}"
+                )))
+            }
+        };
+        Ok(record)
+    }
+
+    // ---- metadata ---------------------------------------------------------
+
+    fn read_metadata(source: &Source) -> Result<Metadata, Error> {
+        let len = source.len()? as usize;
+        let scan = len.min(128 * 1024);
+        let tail = source.read_buf((len - scan) as u64, scan)?;
+        let marker_at = tail
+            .windows(METADATA_START_MARKER.len())
+            .rposition(|w| w == METADATA_START_MARKER)
+            .ok_or(Error::UnknownDb)?;
+        let meta_offset = (len - scan) + marker_at + METADATA_START_MARKER.len();
+
+        // The metadata section is a self-contained data section whose pointers
+        // are relative to its own start.
+        let meta_bytes = source.read_buf(meta_offset as u64, len - meta_offset)?;
+        let meta = MaxMindMetaReader {
+            bytes: &meta_bytes,
+            pointer_base: 0,
+        };
+        let (value, _) = meta.decode(0)?;
+        let map = match value {
+            MmdbValue::Map(m) => m,
+            _ => return Err(Error::UnknownDb),
+        };
+
+        Ok(Metadata {
+            node_count: map.get("node_count").and_then(as_u64).unwrap_or(0) as u32,
+            record_size: map.get("record_size").and_then(as_u64).unwrap_or(0) as u16,
+            ip_version: map.get("ip_version").and_then(as_u64).unwrap_or(6) as u16,
+            database_type: map
+                .get("database_type")
+                .and_then(as_string)
+                .unwrap_or_default(),
+        })
+    }
+
+    // ---- data section decoding -------------------------------------------
+
+    // A persistent view over the whole database: the mmap itself for mmap
+    // sources, or the buffer read once at open for file sources.
+    fn bytes(&self) -> &[u8] {
+        self.source.as_slice()
+    }
+
+    fn decode(&self, offset: usize) -> Result<(MmdbValue, usize), Error> {
+        // Data-section pointers are relative to the start of the data section,
+        // which follows the search tree and the zero separator.
+        let reader = MaxMindMetaReader {
+            bytes: self.bytes(),
+            pointer_base: self.search_tree_size + DATA_SECTION_SEPARATOR_SIZE,
+        };
+        reader.decode(offset)
+    }
+
+    fn map_into_record(&self, value: &MmdbValue, record: &mut LocationRecord) {
+        let map = match value {
+            MmdbValue::Map(m) => m,
+            _ => return,
+        };
+
+        if let Some(MmdbValue::Map(country)) = map.get("country") {
+            let short = country.get("iso_code").and_then(mmdb_string);
+            let long = country
+                .get("names")
+                .and_then(mmdb_en_name)
+                .or_else(|| short.clone());
+            if let (Some(short_name), Some(long_name)) = (short, long) {
+                record.country = Some(Country {
+                    short_name,
+                    long_name,
+                });
+            }
+        }
+
+        if let Some(MmdbValue::Array(subs)) = map.get("subdivisions") {
+            if let Some(MmdbValue::Map(first)) = subs.first() {
+                record.region = first.get("names").and_then(mmdb_en_name);
+            }
+        }
+
+        if let Some(MmdbValue::Map(city)) = map.get("city") {
+            record.city = city.get("names").and_then(mmdb_en_name);
+        }
+
+        if let Some(MmdbValue::Map(location)) = map.get("location") {
+            record.latitude = location.get("latitude").and_then(mmdb_f32);
+            record.longitude = location.get("longitude").and_then(mmdb_f32);
+        }
+    }
+}
+
+/// Iterator over every populated network block in a MaxMind database.
+pub struct MaxMindIter<'a> {
+    db: &'a MaxMindDb,
+    // Pending subtrees as (node, accumulated network bits, depth).
+    stack: Vec<(usize, u128, u8)>,
+    // Set once an error has been yielded so the iterator is fused.
+    done: bool,
+}
+
+impl Iterator for MaxMindIter<'_> {
+    type Item = Result<(IpAddr, IpAddr, LocationRecord), Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let node_count = self.db.metadata.node_count as usize;
+        let total_bits = if self.db.metadata.ip_version == 6 { 128 } else { 32 };
+
+        // Surfaces an error as the final item, fusing the iterator.
+        macro_rules! fail {
+            ($e:expr) => {{
+                self.done = true;
+                return Some(Err($e));
+            }};
+        }
+
+        while let Some((node, prefix, depth)) = self.stack.pop() {
+            match node.cmp(&node_count) {
+                std::cmp::Ordering::Less => {
+                    if depth >= total_bits {
+                        fail!(Error::IoError(
+                            "Corrupt MaxMind search tree: node deeper than the address space"
+                                .to_string()
+                        ));
+                    }
+                    let left = match self.db.read_node(node, 0) {
+                        Ok(n) => n,
+                        Err(e) => fail!(e),
+                    };
+                    let right = match self.db.read_node(node, 1) {
+                        Ok(n) => n,
+                        Err(e) => fail!(e),
+                    };
+                    let bit = 1_u128 << (total_bits - 1 - depth);
+                    // Push right first so the left subtree is visited first.
+                    self.stack.push((right, prefix | bit, depth + 1));
+                    self.stack.push((left, prefix, depth + 1));
+                }
+                std::cmp::Ordering::Equal => continue,
+                std::cmp::Ordering::Greater => {
+                    let offset = node - node_count + self.db.search_tree_size;
+                    let value = match self.db.decode(offset) {
+                        Ok((value, _)) => value,
+                        Err(e) => fail!(e),
+                    };
+                    let (start, end) = mask_bounds(prefix, depth, total_bits);
+                    let (start_ip, end_ip) = to_ip_pair(start, end, total_bits);
+                    let mut record = LocationRecord {
+                        ip: start_ip,
+                        range: Some((start_ip, end_ip)),
+                        ..Default::default()
+                    };
+                    self.db.map_into_record(&value, &mut record);
+                    return Some(Ok((start_ip, end_ip, record)));
+                }
+            }
+        }
+        None
+    }
+}
+
+fn mask_bounds(addr: u128, prefix_len: u8, total_bits: u8) -> (u128, u128) {
+    let host_bits = total_bits - prefix_len;
+    let mask = if host_bits >= 128 {
+        u128::MAX
+    } else {
+        (1_u128 << host_bits) - 1
+    };
+    (addr & !mask, addr | mask)
+}
+
+fn to_ip_pair(start: u128, end: u128, total_bits: u8) -> (IpAddr, IpAddr) {
+    if total_bits == 32 {
+        (
+            IpAddr::V4((start as u32).into()),
+            IpAddr::V4((end as u32).into()),
+        )
+    } else {
+        (IpAddr::V6(start.into()), IpAddr::V6(end.into()))
+    }
+}
+
+// A flat view over a byte slice used both for the metadata section and the
+// data section. Pointer values are offsets from the start of the relevant
+// section: zero for the self-contained metadata buffer, and the data-section
+// base for the data reader (see `pointer_base`).
+struct MaxMindMetaReader<'a> {
+    bytes: &'a [u8],
+    pointer_base: usize,
+}
+
+impl MaxMindMetaReader<'_> {
+    fn decode(&self, offset: usize) -> Result<(MmdbValue, usize), Error> {
+        let ctrl = self.bytes[offset];
+        let mut type_num = ctrl >> 5;
+        let mut offset = offset + 1;
+        if type_num == 0 {
+            // Extended type: real type is the next byte + 7.
+            type_num = self.bytes[offset] + 7;
+            offset += 1;
+        }
+
+        if type_num == 1 {
+            return self.decode_pointer(ctrl, offset);
+        }
+
+        let (size, offset) = self.size_from_ctrl(ctrl, offset)?;
+        match type_num {
+            2 => {
+                let s = String::from_utf8(self.bytes[offset..offset + size].to_vec())?;
+                Ok((MmdbValue::String(s), offset + size))
+            }
+            3 => {
+                let mut buf = [0u8; 8];
+                buf.copy_from_slice(&self.bytes[offset..offset + size]);
+                Ok((MmdbValue::Double(f64::from_be_bytes(buf)), offset + size))
+            }
+            4 => Ok((
+                MmdbValue::Bytes(self.bytes[offset..offset + size].to_vec()),
+                offset + size,
+            )),
+            5 | 6 | 9 | 10 => {
+                Ok((MmdbValue::Uint(self.decode_uint(offset, size)), offset + size))
+            }
+            7 => self.decode_map(offset, size),
+            8 => {
+                let v = self.decode_uint(offset, size) as i32;
+                Ok((MmdbValue::Int(v), offset + size))
+            }
+            11 => self.decode_array(offset, size),
+            14 => Ok((MmdbValue::Boolean(size != 0), offset)),
+            15 => {
+                let mut buf = [0u8; 4];
+                buf.copy_from_slice(&self.bytes[offset..offset + size]);
+                Ok((MmdbValue::Float(f32::from_be_bytes(buf)), offset + size))
+            }
+            // Data cache container / end marker are structural; skip over them.
+            _ => Ok((MmdbValue::Uint(0), offset + size)),
+        }
+    }
+
+    fn size_from_ctrl(&self, ctrl: u8, offset: usize) -> Result<(usize, usize), Error> {
+        let mut size = (ctrl & 0x1f) as usize;
+        let offset = match size {
+            0..=28 => offset,
+            29 => {
+                size = 29 + self.bytes[offset] as usize;
+                offset + 1
+            }
+            30 => {
+                size = 285 + self.decode_uint(offset, 2) as usize;
+                offset + 2
+            }
+            _ => {
+                size = 65_821 + self.decode_uint(offset, 3) as usize;
+                offset + 3
+            }
+        };
+        Ok((size, offset))
+    }
+
+    fn decode_uint(&self, offset: usize, size: usize) -> u64 {
+        let mut value = 0u64;
+        for b in &self.bytes[offset..offset + size] {
+            value = (value << 8) | *b as u64;
+        }
+        value
+    }
+
+    fn decode_pointer(&self, ctrl: u8, offset: usize) -> Result<(MmdbValue, usize), Error> {
+        let pointer_size = ((ctrl >> 3) & 0x3) as usize + 1;
+        let bytes = &self.bytes[offset..offset + pointer_size];
+        let value = match pointer_size {
+            1 => (((ctrl & 0x7) as usize) << 8) | bytes[0] as usize,
+            2 => {
+                2048 + ((((ctrl & 0x7) as usize) << 16)
+                    | ((bytes[0] as usize) << 8)
+                    | bytes[1] as usize)
+            }
+            3 => {
+                526_336
+                    + ((((ctrl & 0x7) as usize) << 24)
+                        | ((bytes[0] as usize) << 16)
+                        | ((bytes[1] as usize) << 8)
+                        | bytes[2] as usize)
+            }
+            _ => {
+                ((bytes[0] as usize) << 24)
+                    | ((bytes[1] as usize) << 16)
+                    | ((bytes[2] as usize) << 8)
+                    | bytes[3] as usize
+            }
+        };
+        let next = offset + pointer_size;
+        let (target, _) = self.decode(value + self.pointer_base)?;
+        Ok((target, next))
+    }
+
+    fn decode_map(&self, mut offset: usize, size: usize) -> Result<(MmdbValue, usize), Error> {
+        let mut map = BTreeMap::new();
+        for _ in 0..size {
+            let (key, next) = self.decode(offset)?;
+            let (value, next) = self.decode(next)?;
+            offset = next;
+            if let MmdbValue::String(k) = key {
+                map.insert(k, value);
+            }
+        }
+        Ok((MmdbValue::Map(map), offset))
+    }
+
+    fn decode_array(&self, mut offset: usize, size: usize) -> Result<(MmdbValue, usize), Error> {
+        let mut array = Vec::with_capacity(size);
+        for _ in 0..size {
+            let (value, next) = self.decode(offset)?;
+            offset = next;
+            array.push(value);
+        }
+        Ok((MmdbValue::Array(array), offset))
+    }
+}
+
+// ---- small value helpers -------------------------------------------------
+
+fn as_u64(v: &MmdbValue) -> Option<u64> {
+    match v {
+        MmdbValue::Uint(n) => Some(*n),
+        _ => None,
+    }
+}
+
+fn as_string(v: &MmdbValue) -> Option<String> {
+    match v {
+        MmdbValue::String(s) => Some(s.clone()),
+        _ => None,
+    }
+}
+
+fn mmdb_string(v: &MmdbValue) -> Option<String> {
+    as_string(v)
+}
+
+fn mmdb_en_name(v: &MmdbValue) -> Option<String> {
+    match v {
+        MmdbValue::Map(names) => names.get("en").and_then(as_string),
+        _ => None,
+    }
+}
+
+fn mmdb_f32(v: &MmdbValue) -> Option<f32> {
+    match v {
+        MmdbValue::Double(d) => Some(*d as f32),
+        MmdbValue::Float(f) => Some(*f),
+        _ => None,
+    }
+}